@@ -1,17 +1,38 @@
 fn main() {
     windows::build! {
         Windows::Win32::{
-            Foundation::{PWSTR, HANDLE},
+            Foundation::{PWSTR, HANDLE, ERROR_CLASS_ALREADY_EXISTS},
             System::{
                 RemoteDesktop::{
                     WTSEnumerateSessionsW, WTS_SESSION_INFOW,
                     WTSOpenServerW, WTSCloseServer,
                     WTSFreeMemory,
-                    WTSQuerySessionInformationW, WTS_INFO_CLASS, WTSCLIENTW
+                    WTSQuerySessionInformationW, WTS_INFO_CLASS, WTSCLIENTW,
+                    WTSDisconnectSession, WTSLogoffSession, WTSSendMessageW,
+                    WTSSessionInfo, WTSClientProtocolType, WTSClientDisplay,
+                    WTSINFOW, WTS_CLIENT_DISPLAY,
+                    WTSRegisterSessionNotification, WTSUnRegisterSessionNotification,
+                    NOTIFY_FOR_THIS_SESSION,
+                    WTS_CONSOLE_CONNECT, WTS_CONSOLE_DISCONNECT,
+                    WTS_REMOTE_CONNECT, WTS_REMOTE_DISCONNECT,
+                    WTS_SESSION_LOGON, WTS_SESSION_LOGOFF,
+                    WTS_SESSION_LOCK, WTS_SESSION_UNLOCK,
+                    WTS_SESSION_REMOTE_CONTROL,
+                    WTSEnumerateProcessesW, WTS_PROCESS_INFOW
                 },
                 SystemInformation::{GetComputerNameExW, COMPUTER_NAME_FORMAT},
                 WindowsProgramming::GetUserNameW,
-                Diagnostics::Debug::GetLastError
+                Diagnostics::Debug::GetLastError,
+                LibraryLoader::GetModuleHandleW,
+                Threading::GetCurrentThreadId,
+            },
+            UI::WindowsAndMessaging::{
+                WNDCLASSEXW, RegisterClassExW, CreateWindowExW, DefWindowProcW,
+                GetMessageW, TranslateMessage, DispatchMessageW, DestroyWindow,
+                PostQuitMessage, PostThreadMessageW,
+                SetWindowLongPtrW, GetWindowLongPtrW,
+                WM_WTSSESSION_CHANGE, WM_DESTROY, WM_QUIT, GWLP_USERDATA, CW_USEDEFAULT,
+                HWND_MESSAGE,
             },
         }
     };
@@ -4,41 +4,197 @@
 
 windows::include_bindings!();
 
+#[cfg(feature = "tokio")]
+mod nonblocking;
+mod snapshot;
+mod watcher;
+
+pub use snapshot::SessionSnapshot;
+pub use watcher::{SessionEvent, SessionWatcher};
+
 use anyhow::{anyhow, Result};
 use log::{info, trace};
-use std::{ffi::c_void, mem, slice};
+use std::{
+    ffi::c_void,
+    mem,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    slice,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 use winsafe::WString;
 use Windows::Win32::{
-    Foundation::{HANDLE, PWSTR},
+    Foundation::{BOOL, FILETIME, HANDLE, PWSTR},
     System::{
         Diagnostics::Debug::GetLastError,
         RemoteDesktop::{
-            WTSClientInfo, WTSCloseServer, WTSEnumerateSessionsW, WTSFreeMemory, WTSOpenServerW,
-            WTSQuerySessionInformationW, WTSCLIENTW, WTS_SESSION_INFOW,
+            WTSClientDisplay, WTSClientInfo, WTSClientProtocolType, WTSCloseServer,
+            WTSDisconnectSession, WTSEnumerateProcessesW, WTSEnumerateSessionsW, WTSFreeMemory,
+            WTSLogoffSession, WTSOpenServerW, WTSQuerySessionInformationW, WTSSendMessageW,
+            WTSSessionInfo, WTSCLIENTW, WTSINFOW, WTS_CLIENT_DISPLAY, WTS_PROCESS_INFOW,
+            WTS_SESSION_INFOW,
         },
         SystemInformation::{GetComputerNameExW, COMPUTER_NAME_FORMAT},
     },
 };
 
+/// Seconds between the Windows FILETIME epoch (1601-01-01) and the Unix epoch.
+const FILETIME_TO_UNIX_EPOCH_SECS: u64 = 11_644_473_600;
+
 /// Remote Server
 pub struct RemoteServer {
-    server_handle: HANDLE,
+    server_handle: ServerHandle,
     /// Vector of sessions info
     sessions_list: Vec<RemoteDesktopSessionInfo>,
 }
 
-impl Drop for RemoteServer {
+/// A `WTSOpenServerW` handle, reference-counted so that background work spawned off
+/// `RemoteServer` (e.g. the polling task behind [`RemoteServer::subscribe`] in the
+/// `tokio` feature) can keep a clone alive and outlive the `RemoteServer` it was
+/// spawned from, without the handle being closed out from under it.
+#[derive(Clone)]
+pub(crate) struct ServerHandle(Arc<ServerHandleInner>);
+
+struct ServerHandleInner {
+    handle: HANDLE,
+    /// `false` for the `WTS_CURRENT_SERVER_HANDLE` pseudo-handle returned by
+    /// [`RemoteServer::local`], which must never be passed to `WTSCloseServer`.
+    owns_handle: bool,
+}
+
+// `HANDLE` is just an opaque value as far as `WTS*` calls are concerned, and every
+// call already takes it by value from any thread; safe to share across threads here.
+unsafe impl Send for ServerHandleInner {}
+unsafe impl Sync for ServerHandleInner {}
+
+impl ServerHandle {
+    fn new(handle: HANDLE, owns_handle: bool) -> Self {
+        Self(Arc::new(ServerHandleInner {
+            handle,
+            owns_handle,
+        }))
+    }
+
+    pub(crate) fn raw(&self) -> HANDLE {
+        self.0.handle
+    }
+}
+
+impl Drop for ServerHandleInner {
     fn drop(&mut self) {
-        unsafe { WTSCloseServer(self.server_handle) };
+        if self.owns_handle {
+            unsafe { WTSCloseServer(self.handle) };
+        }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Session Info
 pub struct RemoteDesktopSessionInfo {
     session_id: u32,
     state: RemoteDesktopSessionState,
     client_info: ClientInfo,
+    /// Protocol used by the client connected to this session.
+    protocol: ProtocolType,
+    /// Resolution and color depth reported by the client, if available.
+    display: Option<DisplayInfo>,
+    /// When the session was connected.
+    #[cfg_attr(feature = "serde", serde(with = "self::serde_time"))]
+    connect_time: Option<SystemTime>,
+    /// When the session was disconnected, if it currently is.
+    #[cfg_attr(feature = "serde", serde(with = "self::serde_time"))]
+    disconnect_time: Option<SystemTime>,
+    /// When the session's user logged on.
+    #[cfg_attr(feature = "serde", serde(with = "self::serde_time"))]
+    logon_time: Option<SystemTime>,
+    /// How long the session has gone without user input.
+    idle_time: Option<Duration>,
+}
+
+impl RemoteDesktopSessionInfo {
+    /// Connected user-name.
+    pub fn user(&self) -> &str {
+        &self.client_info.user
+    }
+
+    /// Connected client's NetBIOS name.
+    pub fn client(&self) -> &str {
+        &self.client_info.client
+    }
+
+    /// Parsed IP address of the connected client, if decodable.
+    pub fn client_ip(&self) -> Option<IpAddr> {
+        self.client_info.client_ip()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Protocol used by a connected client.
+pub enum ProtocolType {
+    /// The session is running on the physical console.
+    Console,
+    /// The session is using the RDP protocol.
+    Rdp,
+    /// A value `WTSClientProtocolType` doesn't document (e.g. `1`, reserved/unused).
+    Unknown(u16),
+}
+
+impl ProtocolType {
+    fn get_variant(id: u16) -> Self {
+        match id {
+            0 => Self::Console,
+            2 => Self::Rdp,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Resolution and color depth reported by a connected client.
+pub struct DisplayInfo {
+    /// Horizontal resolution in pixels.
+    pub horizontal_resolution: u32,
+    /// Vertical resolution in pixels.
+    pub vertical_resolution: u32,
+    /// Color depth, in bits per pixel.
+    pub color_depth: u32,
+}
+
+/// Converts a Win32 `FILETIME` into a `SystemTime`, returning `None` for the zero/unset value.
+fn filetime_to_system_time(ft: FILETIME) -> Option<SystemTime> {
+    let ticks = ((ft.dwHighDateTime as u64) << 32) | ft.dwLowDateTime as u64;
+    if ticks == 0 {
+        return None;
+    }
+    let unix_secs = (ticks / 10_000_000).checked_sub(FILETIME_TO_UNIX_EPOCH_SECS)?;
+    let nanos = ((ticks % 10_000_000) * 100) as u32;
+    Some(UNIX_EPOCH + Duration::new(unix_secs, nanos))
+}
+
+#[cfg(feature = "serde")]
+mod serde_time {
+    //! (De)serializes `Option<SystemTime>` as seconds since the Unix epoch, since
+    //! `SystemTime` itself has no stable serde representation.
+    use super::{Duration, SystemTime, UNIX_EPOCH};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        time: &Option<SystemTime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let secs = time.map(|t| t.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs());
+        secs.serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<SystemTime>, D::Error> {
+        let secs: Option<u64> = Option::deserialize(deserializer)?;
+        Ok(secs.map(|secs| UNIX_EPOCH + Duration::from_secs(secs)))
+    }
 }
 
 impl<'a> Iterator for SessionInfoIter<'a> {
@@ -53,9 +209,10 @@ pub struct SessionInfoIter<'a> {
     internal: &'a Vec<RemoteDesktopSessionInfo>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Client Info
-pub(crate) struct ClientInfo {
+pub struct ClientInfo {
     /// Connected user-name
     pub user: String,
     /// Connected client's NetBIOS name
@@ -64,7 +221,33 @@ pub(crate) struct ClientInfo {
     pub address: (u32, [u16; 31]),
 }
 
-#[derive(Debug, PartialEq)]
+impl ClientInfo {
+    /// Parses `address` into a [`std::net::IpAddr`] per the `WTSCLIENTW` documentation,
+    /// returning `None` when the client address family is unspecified or unrecognized.
+    pub fn client_ip(&self) -> Option<IpAddr> {
+        const AF_INET: u32 = 2;
+        const AF_INET6: u32 = 23;
+        let (family, raw) = self.address;
+        match family {
+            AF_INET => {
+                let octets = [raw[0] as u8, raw[1] as u8, raw[2] as u8, raw[3] as u8];
+                Some(IpAddr::V4(Ipv4Addr::from(octets)))
+            }
+            AF_INET6 => {
+                let mut octets = [0_u8; 16];
+                octets
+                    .iter_mut()
+                    .zip(raw.iter())
+                    .for_each(|(octet, word)| *octet = *word as u8);
+                Some(IpAddr::V6(Ipv6Addr::from(octets)))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Session state
 pub enum RemoteDesktopSessionState {
     /// A user is logged on to the WinStation. This state occurs when a user is signed in and actively connected to the device.
@@ -116,94 +299,463 @@ impl RemoteServer {
         let server_handle = unsafe { WTSOpenServerW(PWSTR(server_name.as_mut_ptr())) };
         trace!("server handle: {:?}", server_handle);
         Ok(Self {
-            server_handle,
+            server_handle: ServerHandle::new(server_handle, true),
             sessions_list: Vec::new(),
         })
     }
 
+    /// Creates a `RemoteServer` for the local machine using the
+    /// `WTS_CURRENT_SERVER_HANDLE` pseudo-handle, without needing a host name and
+    /// without opening a real server handle to close later.
+    pub fn local() -> Self {
+        Self {
+            server_handle: ServerHandle::new(HANDLE(0), false),
+            sessions_list: Vec::new(),
+        }
+    }
+
     /// Fetch information from connected server
     pub fn update_info(&mut self) -> Result<()> {
-        info!("update requested!");
-        let mut sessions: *mut WTS_SESSION_INFOW =
-            unsafe { mem::MaybeUninit::uninit().assume_init() };
-        let mut session_count = 0;
-        let mut sessions_v: Vec<RemoteDesktopSessionInfo> = Vec::new();
-        match unsafe {
-            WTSEnumerateSessionsW(self.server_handle, 0, 1, &mut sessions, &mut session_count)
+        self.sessions_list = enumerate_sessions(self.server_handle.raw())?;
+        Ok(())
+    }
+
+    /// Returns iterator to go through all connections
+    pub fn iter(&self) -> SessionInfoIter {
+        SessionInfoIter {
+            internal: &self.sessions_list,
         }
-        .0
+    }
+
+    /// Disconnects the given session, leaving it running but no longer attached to its client.
+    ///
+    /// When `wait` is `true` this call blocks until the operation completes.
+    pub fn disconnect_session(&self, session_id: u32, wait: bool) -> Result<()> {
+        match unsafe { WTSDisconnectSession(self.server_handle.raw(), session_id, BOOL::from(wait)) }
+            .0
         {
             0 => {
                 let error = unsafe { GetLastError() };
                 Err(anyhow!(
-                    "couldn't read remote-desktop sessions info. error-code: {:?}",
+                    "couldn't disconnect session {}. error-code: {:?}",
+                    session_id,
                     error
                 ))
             }
-            _ => {
-                info!("session count is: {}", session_count);
-                let sessions_list =
-                    unsafe { slice::from_raw_parts(sessions, session_count as usize) };
-                for ss_ptr in sessions_list {
-                    let ss = *ss_ptr;
-                    sessions_v.push(RemoteDesktopSessionInfo {
-                        session_id: ss.SessionId,
-                        state: RemoteDesktopSessionState::get_variant(ss.State.0),
-                        client_info: self.fetch_client_info(ss.SessionId)?,
-                    });
-                }
-                unsafe { WTSFreeMemory(sessions as *mut c_void) };
-                self.sessions_list = sessions_v;
-                Ok(())
+            _ => Ok(()),
+        }
+    }
+
+    /// Logs off the user of the given session, ending it.
+    ///
+    /// When `wait` is `true` this call blocks until the operation completes.
+    pub fn logoff_session(&self, session_id: u32, wait: bool) -> Result<()> {
+        match unsafe { WTSLogoffSession(self.server_handle.raw(), session_id, BOOL::from(wait)) }.0
+        {
+            0 => {
+                let error = unsafe { GetLastError() };
+                Err(anyhow!(
+                    "couldn't log off session {}. error-code: {:?}",
+                    session_id,
+                    error
+                ))
             }
+            _ => Ok(()),
         }
     }
 
-    fn fetch_client_info(&self, session_id: u32) -> Result<ClientInfo> {
-        let mut buffer_ptr = PWSTR::default();
-        let mut byte_count = 0;
+    /// Displays a message box on the given session and waits (up to `timeout`) for the user's response.
+    pub fn send_message(
+        &self,
+        session_id: u32,
+        title: &str,
+        body: &str,
+        style: MsgBoxStyle,
+        timeout: Duration,
+    ) -> Result<MsgBoxResponse> {
+        let mut title = WString::from_str(title);
+        let mut body = WString::from_str(body);
+        let mut response = 0_i32;
         match unsafe {
-            WTSQuerySessionInformationW(
-                self.server_handle,
+            WTSSendMessageW(
+                self.server_handle.raw(),
                 session_id,
-                WTSClientInfo,
-                &mut buffer_ptr,
-                &mut byte_count,
+                PWSTR(title.as_mut_ptr()),
+                (title.buf_len() * mem::size_of::<u16>()) as u32,
+                PWSTR(body.as_mut_ptr()),
+                (body.buf_len() * mem::size_of::<u16>()) as u32,
+                style.as_u32(),
+                timeout.as_secs() as u32,
+                &mut response,
+                BOOL::from(true),
             )
         }
         .0
         {
             0 => {
                 let error = unsafe { GetLastError() };
-                Err(anyhow!("couldn't read user-name. error-code: {:?}", error))
+                Err(anyhow!(
+                    "couldn't send message to session {}. error-code: {:?}",
+                    session_id,
+                    error
+                ))
+            }
+            _ => Ok(MsgBoxResponse::get_variant(response)),
+        }
+    }
+
+    /// Subscribes to session-change notifications (logon/logoff, connect/disconnect,
+    /// lock/unlock, remote-control) instead of having to poll [`Self::update_info`].
+    ///
+    /// Returns a [`SessionWatcher`], a blocking iterator of [`SessionEvent`]s.
+    ///
+    /// Notifications come from the local machine and only for the session the calling
+    /// process runs in; this server's handle is not used.
+    pub fn watch(&self) -> Result<SessionWatcher> {
+        SessionWatcher::new()
+    }
+
+    /// Takes a point-in-time snapshot of the current session list (as of the last
+    /// [`Self::update_info`] call), bundled with the host name and a timestamp.
+    pub fn snapshot(&self) -> Result<SessionSnapshot> {
+        Ok(SessionSnapshot {
+            host_name: get_host_name()?,
+            taken_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            sessions: self.sessions_list.clone(),
+        })
+    }
+
+    /// Lists the processes running under this server, optionally restricted to a
+    /// single session, answering "what is running under each RDP session".
+    pub fn enumerate_processes(&self, session_id: Option<u32>) -> Result<Vec<ProcessInfo>> {
+        let mut processes: *mut WTS_PROCESS_INFOW =
+            unsafe { mem::MaybeUninit::uninit().assume_init() };
+        let mut process_count = 0;
+        match unsafe {
+            WTSEnumerateProcessesW(
+                self.server_handle.raw(),
+                0,
+                1,
+                &mut processes,
+                &mut process_count,
+            )
+        }
+        .0
+        {
+            0 => {
+                let error = unsafe { GetLastError() };
+                Err(anyhow!(
+                    "couldn't enumerate processes. error-code: {:?}",
+                    error
+                ))
             }
             _ => {
-                let client_info_ptr =
-                    unsafe { mem::transmute::<*mut u16, *mut WTSCLIENTW>(buffer_ptr.0) };
-                let client_info = unsafe { *client_info_ptr };
-                trace!(
-                    "client-info of session-id: {} is {:?}",
-                    session_id,
-                    client_info
-                );
-                unsafe { WTSFreeMemory(buffer_ptr.0 as *mut c_void) };
-                let user =
-                    WString::from_wchars_slice(&client_info.UserName[..]).to_string_checked()?;
-                let client =
-                    WString::from_wchars_slice(&client_info.ClientName[..]).to_string_checked()?;
-                Ok(ClientInfo {
-                    user,
-                    client,
-                    address: (client_info.ClientAddressFamily, client_info.ClientAddress),
-                })
+                let processes_list =
+                    unsafe { slice::from_raw_parts(processes, process_count as usize) };
+                let mut processes_v = Vec::new();
+                for process in processes_list {
+                    if !process_matches_session(process.SessionId, session_id) {
+                        continue;
+                    }
+                    let image_name = unsafe { WString::from_wchars_nullt(process.pProcessName.0) }
+                        .to_string_checked()?;
+                    processes_v.push(ProcessInfo {
+                        pid: process.ProcessId,
+                        session_id: process.SessionId,
+                        image_name,
+                    });
+                }
+                unsafe { WTSFreeMemory(processes as *mut c_void) };
+                Ok(processes_v)
             }
         }
     }
+}
 
-    /// Returns iterator to go through all connections
-    pub fn iter(&self) -> SessionInfoIter {
-        SessionInfoIter {
-            internal: &self.sessions_list,
+/// Whether a process belonging to `process_session_id` should be kept under the
+/// `session_id` filter passed to [`RemoteServer::enumerate_processes`] (`None` keeps
+/// everything).
+fn process_matches_session(process_session_id: u32, session_id: Option<u32>) -> bool {
+    match session_id {
+        Some(session_id) => process_session_id == session_id,
+        None => true,
+    }
+}
+
+/// A process running under a session, as returned by [`RemoteServer::enumerate_processes`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProcessInfo {
+    /// Process id.
+    pub pid: u32,
+    /// Id of the session the process is running under.
+    pub session_id: u32,
+    /// Executable image name, e.g. `notepad.exe`.
+    pub image_name: String,
+}
+
+/// Enumerates every session on `server_handle`, fetching each one's client info,
+/// protocol, display, and timing details. Standalone so it can be driven either from
+/// [`RemoteServer::update_info`] directly or off-thread via `spawn_blocking` in the
+/// `tokio` feature.
+pub(crate) fn enumerate_sessions(server_handle: HANDLE) -> Result<Vec<RemoteDesktopSessionInfo>> {
+    info!("update requested!");
+    let mut sessions: *mut WTS_SESSION_INFOW = unsafe { mem::MaybeUninit::uninit().assume_init() };
+    let mut session_count = 0;
+    let mut sessions_v: Vec<RemoteDesktopSessionInfo> = Vec::new();
+    match unsafe { WTSEnumerateSessionsW(server_handle, 0, 1, &mut sessions, &mut session_count) }.0
+    {
+        0 => {
+            let error = unsafe { GetLastError() };
+            Err(anyhow!(
+                "couldn't read remote-desktop sessions info. error-code: {:?}",
+                error
+            ))
+        }
+        _ => {
+            info!("session count is: {}", session_count);
+            let sessions_list = unsafe { slice::from_raw_parts(sessions, session_count as usize) };
+            for ss_ptr in sessions_list {
+                let ss = *ss_ptr;
+                let (connect_time, disconnect_time, logon_time, idle_time) =
+                    fetch_session_times(server_handle, ss.SessionId)?;
+                sessions_v.push(RemoteDesktopSessionInfo {
+                    session_id: ss.SessionId,
+                    state: RemoteDesktopSessionState::get_variant(ss.State.0),
+                    client_info: fetch_client_info(server_handle, ss.SessionId)?,
+                    protocol: fetch_protocol(server_handle, ss.SessionId)?,
+                    display: fetch_display(server_handle, ss.SessionId)?,
+                    connect_time,
+                    disconnect_time,
+                    logon_time,
+                    idle_time,
+                });
+            }
+            unsafe { WTSFreeMemory(sessions as *mut c_void) };
+            Ok(sessions_v)
+        }
+    }
+}
+
+fn fetch_client_info(server_handle: HANDLE, session_id: u32) -> Result<ClientInfo> {
+    let mut buffer_ptr = PWSTR::default();
+    let mut byte_count = 0;
+    match unsafe {
+        WTSQuerySessionInformationW(
+            server_handle,
+            session_id,
+            WTSClientInfo,
+            &mut buffer_ptr,
+            &mut byte_count,
+        )
+    }
+    .0
+    {
+        0 => {
+            let error = unsafe { GetLastError() };
+            Err(anyhow!("couldn't read user-name. error-code: {:?}", error))
+        }
+        _ => {
+            let client_info_ptr =
+                unsafe { mem::transmute::<*mut u16, *mut WTSCLIENTW>(buffer_ptr.0) };
+            let client_info = unsafe { *client_info_ptr };
+            trace!(
+                "client-info of session-id: {} is {:?}",
+                session_id,
+                client_info
+            );
+            unsafe { WTSFreeMemory(buffer_ptr.0 as *mut c_void) };
+            let user =
+                WString::from_wchars_slice(&client_info.UserName[..]).to_string_checked()?;
+            let client =
+                WString::from_wchars_slice(&client_info.ClientName[..]).to_string_checked()?;
+            Ok(ClientInfo {
+                user,
+                client,
+                address: (client_info.ClientAddressFamily, client_info.ClientAddress),
+            })
+        }
+    }
+}
+
+fn fetch_protocol(server_handle: HANDLE, session_id: u32) -> Result<ProtocolType> {
+    let mut buffer_ptr = PWSTR::default();
+    let mut byte_count = 0;
+    match unsafe {
+        WTSQuerySessionInformationW(
+            server_handle,
+            session_id,
+            WTSClientProtocolType,
+            &mut buffer_ptr,
+            &mut byte_count,
+        )
+    }
+    .0
+    {
+        0 => {
+            let error = unsafe { GetLastError() };
+            Err(anyhow!("couldn't read protocol-type. error-code: {:?}", error))
+        }
+        _ => {
+            let protocol = ProtocolType::get_variant(unsafe { *buffer_ptr.0 });
+            unsafe { WTSFreeMemory(buffer_ptr.0 as *mut c_void) };
+            Ok(protocol)
+        }
+    }
+}
+
+fn fetch_display(server_handle: HANDLE, session_id: u32) -> Result<Option<DisplayInfo>> {
+    let mut buffer_ptr = PWSTR::default();
+    let mut byte_count = 0;
+    match unsafe {
+        WTSQuerySessionInformationW(
+            server_handle,
+            session_id,
+            WTSClientDisplay,
+            &mut buffer_ptr,
+            &mut byte_count,
+        )
+    }
+    .0
+    {
+        0 => {
+            let error = unsafe { GetLastError() };
+            Err(anyhow!("couldn't read client-display. error-code: {:?}", error))
+        }
+        _ => {
+            let display_ptr =
+                unsafe { mem::transmute::<*mut u16, *mut WTS_CLIENT_DISPLAY>(buffer_ptr.0) };
+            let display = unsafe { *display_ptr };
+            unsafe { WTSFreeMemory(buffer_ptr.0 as *mut c_void) };
+            if display.HorizontalResolution == 0 && display.VerticalResolution == 0 {
+                Ok(None)
+            } else {
+                Ok(Some(DisplayInfo {
+                    horizontal_resolution: display.HorizontalResolution,
+                    vertical_resolution: display.VerticalResolution,
+                    color_depth: display.ColorDepth,
+                }))
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn fetch_session_times(
+    server_handle: HANDLE,
+    session_id: u32,
+) -> Result<(
+    Option<SystemTime>,
+    Option<SystemTime>,
+    Option<SystemTime>,
+    Option<Duration>,
+)> {
+    let mut buffer_ptr = PWSTR::default();
+    let mut byte_count = 0;
+    match unsafe {
+        WTSQuerySessionInformationW(
+            server_handle,
+            session_id,
+            WTSSessionInfo,
+            &mut buffer_ptr,
+            &mut byte_count,
+        )
+    }
+    .0
+    {
+        0 => {
+            let error = unsafe { GetLastError() };
+            Err(anyhow!("couldn't read session-info. error-code: {:?}", error))
+        }
+        _ => {
+            let info_ptr = unsafe { mem::transmute::<*mut u16, *mut WTSINFOW>(buffer_ptr.0) };
+            let info = unsafe { *info_ptr };
+            unsafe { WTSFreeMemory(buffer_ptr.0 as *mut c_void) };
+            let connect_time = filetime_to_system_time(info.ConnectTime);
+            let disconnect_time = filetime_to_system_time(info.DisconnectTime);
+            let logon_time = filetime_to_system_time(info.LogonTime);
+            let last_input_time = filetime_to_system_time(info.LastInputTime);
+            let current_time = filetime_to_system_time(info.CurrentTime);
+            let idle_time = match (last_input_time, current_time) {
+                (Some(last_input), Some(current)) => current.duration_since(last_input).ok(),
+                _ => None,
+            };
+            Ok((connect_time, disconnect_time, logon_time, idle_time))
+        }
+    }
+}
+
+/// Style of the message box shown by [`RemoteServer::send_message`].
+pub enum MsgBoxStyle {
+    /// A single OK button.
+    Ok,
+    /// OK and Cancel buttons.
+    OkCancel,
+    /// Abort, Retry, and Ignore buttons.
+    AbortRetryIgnore,
+    /// Yes, No, and Cancel buttons.
+    YesNoCancel,
+    /// Yes and No buttons.
+    YesNo,
+    /// Retry and Cancel buttons.
+    RetryCancel,
+    /// Cancel, Try Again, and Continue buttons.
+    CancelTryContinue,
+}
+
+impl MsgBoxStyle {
+    fn as_u32(&self) -> u32 {
+        match self {
+            Self::Ok => 0,
+            Self::OkCancel => 1,
+            Self::AbortRetryIgnore => 2,
+            Self::YesNoCancel => 3,
+            Self::YesNo => 4,
+            Self::RetryCancel => 5,
+            Self::CancelTryContinue => 6,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+/// The button the user selected to dismiss a [`RemoteServer::send_message`] dialog.
+pub enum MsgBoxResponse {
+    /// The OK button.
+    Ok,
+    /// The Cancel button.
+    Cancel,
+    /// The Abort button.
+    Abort,
+    /// The Retry button.
+    Retry,
+    /// The Ignore button.
+    Ignore,
+    /// The Yes button.
+    Yes,
+    /// The No button.
+    No,
+    /// The Try Again button.
+    TryAgain,
+    /// The Continue button.
+    Continue,
+    /// The dialog timed out before the user responded.
+    Timeout,
+}
+
+impl MsgBoxResponse {
+    fn get_variant(id: i32) -> Self {
+        match id {
+            1 => Self::Ok,
+            2 => Self::Cancel,
+            3 => Self::Abort,
+            4 => Self::Retry,
+            5 => Self::Ignore,
+            6 => Self::Yes,
+            7 => Self::No,
+            10 => Self::TryAgain,
+            11 => Self::Continue,
+            32000 => Self::Timeout,
+            _ => unreachable!(),
         }
     }
 }
@@ -224,3 +776,148 @@ pub fn get_host_name() -> Result<String> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client_info_with_address(family: u32, address: [u16; 31]) -> ClientInfo {
+        ClientInfo {
+            user: String::new(),
+            client: String::new(),
+            address: (family, address),
+        }
+    }
+
+    #[test]
+    fn client_ip_decodes_ipv4() {
+        let mut address = [0_u16; 31];
+        address[..4].copy_from_slice(&[192, 168, 1, 42]);
+        let client_info = client_info_with_address(2, address);
+        assert_eq!(
+            client_info.client_ip(),
+            Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 42)))
+        );
+    }
+
+    #[test]
+    fn client_ip_decodes_ipv6() {
+        let mut address = [0_u16; 31];
+        address[..16].copy_from_slice(&[
+            0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01,
+        ]);
+        let client_info = client_info_with_address(23, address);
+        assert_eq!(
+            client_info.client_ip(),
+            Some(IpAddr::V6(Ipv6Addr::new(0x2001, 0x0db8, 0, 0, 0, 0, 0, 1)))
+        );
+    }
+
+    #[test]
+    fn client_ip_is_none_for_unknown_family() {
+        let client_info = client_info_with_address(0, [0_u16; 31]);
+        assert_eq!(client_info.client_ip(), None);
+    }
+
+    #[test]
+    fn protocol_type_unknown_id_does_not_panic() {
+        assert_eq!(ProtocolType::get_variant(0), ProtocolType::Console);
+        assert_eq!(ProtocolType::get_variant(2), ProtocolType::Rdp);
+        assert_eq!(ProtocolType::get_variant(1), ProtocolType::Unknown(1));
+        assert_eq!(ProtocolType::get_variant(99), ProtocolType::Unknown(99));
+    }
+
+    #[test]
+    fn filetime_to_system_time_is_none_for_zero_ticks() {
+        let ft = FILETIME {
+            dwLowDateTime: 0,
+            dwHighDateTime: 0,
+        };
+        assert_eq!(filetime_to_system_time(ft), None);
+    }
+
+    #[test]
+    fn filetime_to_system_time_is_none_before_unix_epoch() {
+        // A single tick (100ns), i.e. 1601-01-01T00:00:00.0000001, predates the Unix
+        // epoch by just under FILETIME_TO_UNIX_EPOCH_SECS and must not underflow.
+        let ft = FILETIME {
+            dwLowDateTime: 1,
+            dwHighDateTime: 0,
+        };
+        assert_eq!(filetime_to_system_time(ft), None);
+    }
+
+    #[test]
+    fn filetime_to_system_time_converts_known_value() {
+        // 1970-01-01T00:00:01Z, i.e. exactly FILETIME_TO_UNIX_EPOCH_SECS + 1 seconds
+        // after the FILETIME epoch, expressed in 100ns ticks.
+        let ticks = (FILETIME_TO_UNIX_EPOCH_SECS + 1) * 10_000_000;
+        let ft = FILETIME {
+            dwLowDateTime: ticks as u32,
+            dwHighDateTime: (ticks >> 32) as u32,
+        };
+        assert_eq!(
+            filetime_to_system_time(ft),
+            Some(UNIX_EPOCH + Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn msg_box_style_as_u32_matches_documented_values() {
+        assert_eq!(MsgBoxStyle::Ok.as_u32(), 0);
+        assert_eq!(MsgBoxStyle::OkCancel.as_u32(), 1);
+        assert_eq!(MsgBoxStyle::AbortRetryIgnore.as_u32(), 2);
+        assert_eq!(MsgBoxStyle::YesNoCancel.as_u32(), 3);
+        assert_eq!(MsgBoxStyle::YesNo.as_u32(), 4);
+        assert_eq!(MsgBoxStyle::RetryCancel.as_u32(), 5);
+        assert_eq!(MsgBoxStyle::CancelTryContinue.as_u32(), 6);
+    }
+
+    #[test]
+    fn msg_box_response_get_variant_matches_documented_ids() {
+        assert_eq!(MsgBoxResponse::get_variant(1), MsgBoxResponse::Ok);
+        assert_eq!(MsgBoxResponse::get_variant(2), MsgBoxResponse::Cancel);
+        assert_eq!(MsgBoxResponse::get_variant(3), MsgBoxResponse::Abort);
+        assert_eq!(MsgBoxResponse::get_variant(4), MsgBoxResponse::Retry);
+        assert_eq!(MsgBoxResponse::get_variant(5), MsgBoxResponse::Ignore);
+        assert_eq!(MsgBoxResponse::get_variant(6), MsgBoxResponse::Yes);
+        assert_eq!(MsgBoxResponse::get_variant(7), MsgBoxResponse::No);
+        assert_eq!(MsgBoxResponse::get_variant(10), MsgBoxResponse::TryAgain);
+        assert_eq!(MsgBoxResponse::get_variant(11), MsgBoxResponse::Continue);
+        assert_eq!(MsgBoxResponse::get_variant(32000), MsgBoxResponse::Timeout);
+    }
+
+    #[test]
+    fn process_matches_session_with_no_filter_keeps_everything() {
+        assert!(process_matches_session(7, None));
+        assert!(process_matches_session(0, None));
+    }
+
+    #[test]
+    fn process_matches_session_with_filter_keeps_only_matching_session() {
+        assert!(process_matches_session(7, Some(7)));
+        assert!(!process_matches_session(7, Some(8)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_time_round_trips_some_and_none() {
+        #[derive(serde::Serialize, serde::Deserialize)]
+        struct Wrapper {
+            #[serde(with = "serde_time")]
+            time: Option<SystemTime>,
+        }
+
+        let some_time = Wrapper {
+            time: Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+        };
+        let json = serde_json::to_string(&some_time).unwrap();
+        let round_tripped: Wrapper = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_tripped.time, some_time.time);
+
+        let no_time = Wrapper { time: None };
+        let json = serde_json::to_string(&no_time).unwrap();
+        let round_tripped: Wrapper = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_tripped.time, None);
+    }
+}
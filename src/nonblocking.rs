@@ -0,0 +1,60 @@
+//! Non-blocking, `tokio`-based variant of the synchronous WTS calls, for callers that
+//! can't afford to stall the calling task while enumerating sessions on a slow or
+//! unreachable remote server.
+
+use crate::{enumerate_sessions, RemoteDesktopSessionInfo, RemoteServer};
+use anyhow::Result;
+use log::{error, trace};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+
+impl RemoteServer {
+    /// Asynchronous version of [`Self::update_info`]; the blocking `WTSEnumerateSessionsW`/
+    /// `WTSQuerySessionInformationW` calls run on the blocking thread pool via
+    /// [`tokio::task::spawn_blocking`] instead of stalling the caller's task.
+    pub async fn update_info_async(&mut self) -> Result<()> {
+        let handle = self.server_handle.clone();
+        let sessions =
+            tokio::task::spawn_blocking(move || enumerate_sessions(handle.raw())).await??;
+        self.sessions_list = sessions;
+        Ok(())
+    }
+
+    /// Polls the server every `interval` and yields the full session list each time,
+    /// so a caller can `while let Some(sessions) = stream.next().await` instead of
+    /// repeatedly calling [`Self::update_info`].
+    ///
+    /// The returned stream holds its own reference-counted clone of the underlying
+    /// server handle, so it keeps polling correctly even if this `RemoteServer` is
+    /// dropped before the stream is.
+    pub fn subscribe(
+        &self,
+        interval: Duration,
+    ) -> impl Stream<Item = Vec<RemoteDesktopSessionInfo>> {
+        let handle = self.server_handle.clone();
+        let (tx, rx) = mpsc::channel(1);
+        tokio::spawn(async move {
+            loop {
+                let handle = handle.clone();
+                match tokio::task::spawn_blocking(move || enumerate_sessions(handle.raw())).await {
+                    Ok(Ok(sessions)) if tx.send(sessions).await.is_ok() => {}
+                    Ok(Ok(_)) => {
+                        trace!("session-subscription receiver dropped, stopping poll loop");
+                        break;
+                    }
+                    Ok(Err(error)) => {
+                        error!("session-subscription poll failed, stopping: {:?}", error);
+                        break;
+                    }
+                    Err(error) => {
+                        error!("session-subscription poll task panicked, stopping: {:?}", error);
+                        break;
+                    }
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+        ReceiverStream::new(rx)
+    }
+}
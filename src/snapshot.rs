@@ -0,0 +1,33 @@
+//! On-disk session snapshots, for logging/auditing tools that periodically dump and
+//! later diff the state of one or more servers.
+
+use crate::RemoteDesktopSessionInfo;
+
+/// A point-in-time snapshot of a server's sessions, suitable for persisting to disk
+/// via [`Self::write_json`] and re-loading later via [`Self::read_json`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionSnapshot {
+    /// Host name of the server the snapshot was taken from.
+    pub host_name: String,
+    /// When the snapshot was taken, in seconds since the Unix epoch.
+    pub taken_at: u64,
+    /// Sessions present on the server at the time of the snapshot.
+    pub sessions: Vec<RemoteDesktopSessionInfo>,
+}
+
+#[cfg(feature = "serde")]
+impl SessionSnapshot {
+    /// Writes this snapshot to `path` as pretty-printed JSON.
+    pub fn write_json<P: AsRef<std::path::Path>>(&self, path: P) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Reads a snapshot previously written by [`Self::write_json`].
+    pub fn read_json<P: AsRef<std::path::Path>>(path: P) -> anyhow::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
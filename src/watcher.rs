@@ -0,0 +1,246 @@
+//! Event-driven session-change notifications, built on a hidden message-only window
+//! pumping `WM_WTSSESSION_CHANGE`.
+
+use anyhow::{anyhow, Result};
+use log::trace;
+use std::{
+    sync::mpsc::{channel, Receiver, Sender},
+    thread::{self, JoinHandle},
+};
+use Windows::Win32::{
+    Foundation::{ERROR_CLASS_ALREADY_EXISTS, HWND, LPARAM, LRESULT, PWSTR, WPARAM},
+    System::{
+        Diagnostics::Debug::GetLastError,
+        LibraryLoader::GetModuleHandleW,
+        RemoteDesktop::{
+            WTSRegisterSessionNotification, WTSUnRegisterSessionNotification,
+            NOTIFY_FOR_THIS_SESSION, WTS_CONSOLE_CONNECT, WTS_CONSOLE_DISCONNECT,
+            WTS_REMOTE_CONNECT, WTS_REMOTE_DISCONNECT, WTS_SESSION_LOCK, WTS_SESSION_LOGOFF,
+            WTS_SESSION_LOGON, WTS_SESSION_REMOTE_CONTROL, WTS_SESSION_UNLOCK,
+        },
+        Threading::GetCurrentThreadId,
+    },
+    UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, GetMessageW,
+        GetWindowLongPtrW, PostQuitMessage, PostThreadMessageW, RegisterClassExW,
+        SetWindowLongPtrW, TranslateMessage, CW_USEDEFAULT, GWLP_USERDATA, HWND_MESSAGE,
+        WM_DESTROY, WM_QUIT, WM_WTSSESSION_CHANGE, WNDCLASSEXW,
+    },
+};
+use winsafe::WString;
+
+/// A session-change event delivered by [`crate::RemoteServer::watch`].
+#[derive(Debug, PartialEq)]
+pub enum SessionEvent {
+    /// A user logged on to the session.
+    Logon {
+        /// The session that changed.
+        session_id: u32,
+    },
+    /// A user logged off the session.
+    Logoff {
+        /// The session that changed.
+        session_id: u32,
+    },
+    /// A client connected to the session (console or remote).
+    Connect {
+        /// The session that changed.
+        session_id: u32,
+    },
+    /// A client disconnected from the session (console or remote).
+    Disconnect {
+        /// The session that changed.
+        session_id: u32,
+    },
+    /// The session was locked.
+    SessionLock {
+        /// The session that changed.
+        session_id: u32,
+    },
+    /// The session was unlocked.
+    SessionUnlock {
+        /// The session that changed.
+        session_id: u32,
+    },
+    /// The session became a remote-control target.
+    RemoteControl {
+        /// The session that changed.
+        session_id: u32,
+    },
+}
+
+impl SessionEvent {
+    fn from_notification(code: u32, session_id: u32) -> Option<Self> {
+        match code {
+            WTS_CONSOLE_CONNECT | WTS_REMOTE_CONNECT => Some(Self::Connect { session_id }),
+            WTS_CONSOLE_DISCONNECT | WTS_REMOTE_DISCONNECT => {
+                Some(Self::Disconnect { session_id })
+            }
+            WTS_SESSION_LOGON => Some(Self::Logon { session_id }),
+            WTS_SESSION_LOGOFF => Some(Self::Logoff { session_id }),
+            WTS_SESSION_LOCK => Some(Self::SessionLock { session_id }),
+            WTS_SESSION_UNLOCK => Some(Self::SessionUnlock { session_id }),
+            WTS_SESSION_REMOTE_CONTROL => Some(Self::RemoteControl { session_id }),
+            _ => None,
+        }
+    }
+}
+
+/// A blocking iterator of [`SessionEvent`]s, delivered as they happen rather than by
+/// polling `update_info`. The watcher owns a dedicated thread that pumps a hidden
+/// message-only window; dropping it tears the window and registration down.
+pub struct SessionWatcher {
+    receiver: Receiver<SessionEvent>,
+    thread: Option<JoinHandle<()>>,
+    /// OS thread id of the message-loop thread, so `Drop` can nudge it out of its
+    /// blocking `GetMessageW` call.
+    thread_id: u32,
+}
+
+impl SessionWatcher {
+    pub(crate) fn new() -> Result<Self> {
+        let (sender, receiver) = channel();
+        let (ready_tx, ready_rx) = channel::<Result<u32>>();
+        let thread = thread::spawn(move || run_message_loop(sender, ready_tx));
+        let thread_id = ready_rx
+            .recv()
+            .map_err(|_| anyhow!("session watcher thread exited before it was ready"))??;
+        Ok(Self {
+            receiver,
+            thread: Some(thread),
+            thread_id,
+        })
+    }
+}
+
+impl Iterator for SessionWatcher {
+    type Item = SessionEvent;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl Drop for SessionWatcher {
+    fn drop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            // GetMessageW blocks the message-loop thread indefinitely; nothing posts
+            // WM_QUIT to it on its own, so nudge it out with a thread message before
+            // joining, or this would hang the dropping thread forever.
+            unsafe { PostThreadMessageW(self.thread_id, WM_QUIT, WPARAM(0), LPARAM(0)) };
+            let _ = thread.join();
+        }
+    }
+}
+
+struct WindowState {
+    sender: Sender<SessionEvent>,
+}
+
+fn run_message_loop(sender: Sender<SessionEvent>, ready_tx: Sender<Result<u32>>) {
+    let thread_id = unsafe { GetCurrentThreadId() };
+    match create_watcher_window(sender) {
+        Ok(hwnd) => {
+            ready_tx.send(Ok(thread_id)).ok();
+            pump_messages();
+            unsafe { DestroyWindow(hwnd) };
+        }
+        Err(error) => {
+            ready_tx.send(Err(error)).ok();
+        }
+    }
+}
+
+fn create_watcher_window(sender: Sender<SessionEvent>) -> Result<HWND> {
+    let instance = unsafe { GetModuleHandleW(PWSTR::default()) };
+    let mut class_name = WString::from_str("rdc_connections_session_watcher");
+    let wnd_class = WNDCLASSEXW {
+        cbSize: std::mem::size_of::<WNDCLASSEXW>() as u32,
+        lpfnWndProc: Some(wnd_proc),
+        hInstance: instance,
+        lpszClassName: PWSTR(class_name.as_mut_ptr()),
+        ..Default::default()
+    };
+    if unsafe { RegisterClassExW(&wnd_class) } == 0 {
+        let error = unsafe { GetLastError() };
+        // Multiple `RemoteServer::watch()` calls in the same process (another host,
+        // a watcher re-created after the first was dropped, a retry) all try to
+        // register the same class name; that's expected and not a real failure.
+        if error != ERROR_CLASS_ALREADY_EXISTS {
+            return Err(anyhow!(
+                "couldn't register session-watcher window class. error-code: {:?}",
+                error
+            ));
+        }
+    }
+
+    let hwnd = unsafe {
+        CreateWindowExW(
+            Default::default(),
+            PWSTR(class_name.as_mut_ptr()),
+            PWSTR(class_name.as_mut_ptr()),
+            Default::default(),
+            CW_USEDEFAULT,
+            CW_USEDEFAULT,
+            CW_USEDEFAULT,
+            CW_USEDEFAULT,
+            HWND_MESSAGE,
+            None,
+            instance,
+            std::ptr::null_mut(),
+        )
+    };
+    if hwnd.is_invalid() {
+        return Err(anyhow!("couldn't create session-watcher message window"));
+    }
+
+    let state = Box::into_raw(Box::new(WindowState { sender }));
+    unsafe { SetWindowLongPtrW(hwnd, GWLP_USERDATA, state as isize) };
+
+    match unsafe { WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION) }.0 {
+        0 => {
+            unsafe { DestroyWindow(hwnd) };
+            Err(anyhow!("couldn't register for session notifications"))
+        }
+        _ => Ok(hwnd),
+    }
+}
+
+fn pump_messages() {
+    let mut msg = unsafe { std::mem::zeroed() };
+    loop {
+        let result = unsafe { GetMessageW(&mut msg, HWND::default(), 0, 0) }.0;
+        if result <= 0 {
+            break;
+        }
+        unsafe {
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    }
+}
+
+extern "system" fn wnd_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    match msg {
+        WM_WTSSESSION_CHANGE => {
+            let state = unsafe { GetWindowLongPtrW(hwnd, GWLP_USERDATA) } as *const WindowState;
+            if let Some(state) = unsafe { state.as_ref() } {
+                let session_id = lparam.0 as u32;
+                if let Some(event) = SessionEvent::from_notification(wparam.0 as u32, session_id) {
+                    trace!("session-watcher event: {:?}", event);
+                    state.sender.send(event).ok();
+                }
+            }
+            LRESULT(0)
+        }
+        WM_DESTROY => {
+            let state = unsafe { GetWindowLongPtrW(hwnd, GWLP_USERDATA) } as *mut WindowState;
+            if !state.is_null() {
+                unsafe { WTSUnRegisterSessionNotification(hwnd) };
+                unsafe { drop(Box::from_raw(state)) };
+            }
+            unsafe { PostQuitMessage(0) };
+            LRESULT(0)
+        }
+        _ => unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) },
+    }
+}